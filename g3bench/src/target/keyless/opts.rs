@@ -0,0 +1,1336 @@
+/*
+ * Copyright 2023 ByteDance and/or its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command as ProcessCommand, Stdio};
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context};
+use clap::{value_parser, Arg, ArgAction, ArgGroup, ArgMatches, Command, ValueHint};
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::derive::Deriver;
+use openssl::ec::{EcGroup, EcKey, EcPoint, PointConversionForm};
+use openssl::encrypt::{Decrypter, Encrypter};
+use openssl::hash::{DigestBytes, MessageDigest};
+use openssl::nid::Nid;
+use openssl::pkey::{Id, PKey, Private, Public};
+use openssl::rsa::{Padding, RsaPrivateKeyBuilder};
+use openssl::sign::{RsaPssSaltlen, Signer, Verifier};
+use openssl::x509::X509;
+use serde_json::Value;
+
+const ARG_CERT: &str = "cert";
+const ARG_PKEY: &str = "key";
+const ARG_SIGNING_HELPER: &str = "signing-helper";
+const ARG_BLIND_SIGN: &str = "blind-sign";
+const ARG_BLIND_CLIENT: &str = "blind-client";
+const ARG_BLIND_RANDOMIZED: &str = "blind-randomized";
+const ARG_PSS_SALT_LEN: &str = "pss-salt-len";
+const ARG_MGF1_DIGEST: &str = "mgf1-digest";
+const ARG_VERIFY: &str = "verify";
+const ARG_ECDH: &str = "ecdh";
+const ARG_KEY_COMPONENTS: &str = "key-components";
+const ARG_KEY_JWK: &str = "key-jwk";
+const ARG_RSA_PRIVATE_ENCRYPT: &str = "rsa-private-encrypt";
+const ARG_RSA_PUBLIC_DECRYPT: &str = "rsa-public-decrypt";
+const ARG_SIGN: &str = "sign";
+const ARG_DECRYPT: &str = "decrypt";
+const ARG_ENCRYPT: &str = "encrypt";
+const ARG_DIGEST_TYPE: &str = "digest-type";
+const ARG_RSA_PADDING: &str = "rsa-padding";
+const ARG_PAYLOAD: &str = "payload";
+const ARG_DUMP_RESULT: &str = "dump-result";
+
+const DIGEST_TYPES: [&str; 6] = ["md5sha1", "sha1", "sha224", "sha256", "sha384", "sha512"];
+const RSA_PADDING_VALUES: [&str; 5] = ["PKCS1", "OAEP", "PSS", "X931", "NONE"];
+
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) enum KeylessRsaPadding {
+    #[default]
+    Pkcs1,
+    Oaep,
+    Pss,
+    X931,
+    None,
+}
+
+impl FromStr for KeylessRsaPadding {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pkcs1" => Ok(KeylessRsaPadding::Pkcs1),
+            "oaep" => Ok(KeylessRsaPadding::Oaep),
+            "pss" => Ok(KeylessRsaPadding::Pss),
+            "x931" => Ok(KeylessRsaPadding::X931),
+            "none" => Ok(KeylessRsaPadding::None),
+            _ => Err(anyhow!("unsupported rsa padding type {s}")),
+        }
+    }
+}
+
+impl From<KeylessRsaPadding> for Padding {
+    fn from(value: KeylessRsaPadding) -> Self {
+        match value {
+            KeylessRsaPadding::None => Padding::NONE,
+            KeylessRsaPadding::Pkcs1 => Padding::PKCS1,
+            KeylessRsaPadding::Oaep => Padding::PKCS1_OAEP,
+            KeylessRsaPadding::Pss => Padding::from_raw(6),
+            KeylessRsaPadding::X931 => Padding::from_raw(5),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum KeylessSignDigest {
+    Md5Sha1,
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl KeylessSignDigest {
+    fn check_payload(&self, payload: &[u8]) -> anyhow::Result<()> {
+        let digest = MessageDigest::from(*self);
+        if digest.size() != payload.len() {
+            return Err(anyhow!(
+                "payload size {} not match digest size {}",
+                payload.len(),
+                digest.size()
+            ));
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            KeylessSignDigest::Md5Sha1 => "MD5SHA1",
+            KeylessSignDigest::Sha1 => "SHA1",
+            KeylessSignDigest::Sha224 => "SHA224",
+            KeylessSignDigest::Sha256 => "SHA256",
+            KeylessSignDigest::Sha384 => "SHA384",
+            KeylessSignDigest::Sha512 => "SHA512",
+        }
+    }
+}
+
+impl FromStr for KeylessSignDigest {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "md5sha1" => Ok(KeylessSignDigest::Md5Sha1),
+            "sha1" => Ok(KeylessSignDigest::Sha1),
+            "sha224" => Ok(KeylessSignDigest::Sha224),
+            "sha256" => Ok(KeylessSignDigest::Sha256),
+            "sha384" => Ok(KeylessSignDigest::Sha384),
+            "sha512" => Ok(KeylessSignDigest::Sha512),
+            _ => Err(anyhow!("unsupported digest type {s}")),
+        }
+    }
+}
+
+impl From<KeylessSignDigest> for MessageDigest {
+    fn from(value: KeylessSignDigest) -> Self {
+        match value {
+            KeylessSignDigest::Md5Sha1 => MessageDigest::from_nid(Nid::MD5_SHA1).unwrap(),
+            KeylessSignDigest::Sha1 => MessageDigest::sha1(),
+            KeylessSignDigest::Sha224 => MessageDigest::sha224(),
+            KeylessSignDigest::Sha256 => MessageDigest::sha256(),
+            KeylessSignDigest::Sha384 => MessageDigest::sha384(),
+            KeylessSignDigest::Sha512 => MessageDigest::sha512(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum KeylessAction {
+    RsaSign(KeylessSignDigest, KeylessRsaPadding),
+    EcdsaSign(KeylessSignDigest),
+    Ed25519Sign,
+    RsaDecrypt(KeylessRsaPadding),
+    RsaEncrypt(KeylessRsaPadding),
+    Encrypt,
+    Decrypt,
+    RsaPrivateEncrypt(KeylessRsaPadding),
+    RsaPublicDecrypt(KeylessRsaPadding),
+    RsaBlindSign,
+    RsaBlindClient(KeylessSignDigest, KeylessRsaPadding, bool),
+    Ecdh,
+}
+
+pub(super) trait AppendKeylessArgs {
+    fn append_keyless_args(self) -> Self;
+}
+
+pub(super) struct KeylessGlobalArgs {
+    pub(super) cert: X509,
+    cert_path: PathBuf,
+    public_key: PKey<Public>,
+    pub(super) private_key: Option<PKey<Private>>,
+    signing_helper: Option<PathBuf>,
+    pss_salt_len: Option<RsaPssSaltlen>,
+    mgf1_md: Option<MessageDigest>,
+    pub(super) action: KeylessAction,
+    pub(super) payload: Vec<u8>,
+    verify: bool,
+    dump_result: bool,
+}
+
+impl KeylessGlobalArgs {
+    pub(super) fn parse_args(args: &ArgMatches) -> anyhow::Result<Self> {
+        let Some(file) = args.get_one::<PathBuf>(ARG_CERT) else {
+            unreachable!();
+        };
+        let cert = crate::target::tls::load_certs(file)?.pop().unwrap();
+        let pkey = cert
+            .public_key()
+            .map_err(|e| anyhow!("failed to fetch pubkey: {e}"))?;
+
+        let payload_str = args.get_one::<String>(ARG_PAYLOAD).unwrap();
+        let payload = hex::decode(payload_str)
+            .map_err(|e| anyhow!("the payload string is not valid hex string: {e}"))?;
+
+        let rsa_padding = if let Some(s) = args.get_one::<String>(ARG_RSA_PADDING) {
+            KeylessRsaPadding::from_str(s)?
+        } else {
+            KeylessRsaPadding::default()
+        };
+
+        let action = if args.get_flag(ARG_SIGN) {
+            let digest_str = args.get_one::<String>(ARG_DIGEST_TYPE).unwrap();
+            let digest_type = KeylessSignDigest::from_str(digest_str)?;
+
+            match pkey.id() {
+                Id::RSA => {
+                    digest_type.check_payload(payload.as_slice())?;
+                    KeylessAction::RsaSign(digest_type, rsa_padding)
+                }
+                Id::EC => {
+                    digest_type.check_payload(payload.as_slice())?;
+                    KeylessAction::EcdsaSign(digest_type)
+                }
+                Id::ED25519 => KeylessAction::Ed25519Sign,
+                id => return Err(anyhow!("unsupported public key type {id:?}")),
+            }
+        } else if args.get_flag(ARG_DECRYPT) {
+            match pkey.id() {
+                Id::RSA => KeylessAction::RsaDecrypt(rsa_padding),
+                _ => KeylessAction::Decrypt,
+            }
+        } else if args.get_flag(ARG_ENCRYPT) {
+            match pkey.id() {
+                Id::RSA => KeylessAction::RsaEncrypt(rsa_padding),
+                _ => KeylessAction::Encrypt,
+            }
+        } else if args.get_flag(ARG_RSA_PRIVATE_ENCRYPT) {
+            KeylessAction::RsaPrivateEncrypt(rsa_padding)
+        } else if args.get_flag(ARG_RSA_PUBLIC_DECRYPT) {
+            KeylessAction::RsaPublicDecrypt(rsa_padding)
+        } else if args.get_flag(ARG_BLIND_SIGN) {
+            KeylessAction::RsaBlindSign
+        } else if args.get_flag(ARG_BLIND_CLIENT) {
+            let digest_str = args.get_one::<String>(ARG_DIGEST_TYPE).unwrap();
+            let digest_type = KeylessSignDigest::from_str(digest_str)?;
+            let randomized = args.get_flag(ARG_BLIND_RANDOMIZED);
+            KeylessAction::RsaBlindClient(digest_type, rsa_padding, randomized)
+        } else if args.get_flag(ARG_ECDH) {
+            match pkey.id() {
+                Id::EC => KeylessAction::Ecdh,
+                id => return Err(anyhow!("ecdh requires an EC key, got {id:?}")),
+            }
+        } else {
+            return Err(anyhow!("no keyless action set"));
+        };
+
+        let pss_salt_len = match args.get_one::<String>(ARG_PSS_SALT_LEN) {
+            Some(s) => Some(parse_pss_salt_len(s)?),
+            None => None,
+        };
+        let mgf1_md = match args.get_one::<String>(ARG_MGF1_DIGEST) {
+            Some(s) => Some(KeylessSignDigest::from_str(s)?.into()),
+            None => None,
+        };
+
+        let verify = args.get_flag(ARG_VERIFY);
+        let dump_result = args.get_flag(ARG_DUMP_RESULT);
+
+        let mut key_args = KeylessGlobalArgs {
+            cert,
+            cert_path: file.clone(),
+            public_key: pkey,
+            private_key: None,
+            signing_helper: args.get_one::<PathBuf>(ARG_SIGNING_HELPER).cloned(),
+            pss_salt_len,
+            mgf1_md,
+            action,
+            payload,
+            verify,
+            dump_result,
+        };
+
+        if let Some(file) = args.get_one::<PathBuf>(ARG_PKEY) {
+            let key = crate::target::tls::load_key(file)?;
+            key_args.private_key = Some(key);
+        } else if let Some(file) = args.get_one::<PathBuf>(ARG_KEY_COMPONENTS) {
+            key_args.private_key = Some(load_key_components(file)?);
+        } else if let Some(file) = args.get_one::<PathBuf>(ARG_KEY_JWK) {
+            key_args.private_key = Some(load_key_jwk(file)?);
+        }
+
+        Ok(key_args)
+    }
+
+    pub(super) fn dump_result(&self, task_id: usize, data: Vec<u8>) {
+        if self.dump_result {
+            let hex_str = hex::encode(data);
+            println!("== Output of task {task_id}:\n{hex_str}");
+        }
+    }
+
+    pub(super) fn get_public_key_digest(&self) -> anyhow::Result<DigestBytes> {
+        let pkey = self
+            .cert
+            .public_key()
+            .map_err(|e| anyhow!("no public key found in cert: {e}"))?;
+        if let Ok(rsa) = pkey.rsa() {
+            let hex = rsa
+                .n()
+                .to_hex_str()
+                .map_err(|e| anyhow!("failed to get hex string of rsa modulus: {e}"))?;
+            openssl::hash::hash(MessageDigest::sha256(), hex.as_bytes())
+                .map_err(|e| anyhow!("public key digest hash error: {e}"))
+        } else if let Ok(ec) = pkey.ec_key() {
+            let group = ec.group();
+            let point = ec.public_key();
+            let mut ctx = BigNumContext::new_secure().unwrap();
+            let bytes = point
+                .to_bytes(group, PointConversionForm::COMPRESSED, &mut ctx)
+                .unwrap();
+            let hex = hex::encode(bytes);
+            openssl::hash::hash(MessageDigest::sha256(), hex.as_bytes())
+                .map_err(|e| anyhow!("public key digest hash error: {e}"))
+        } else {
+            Err(anyhow!("unsupported public type: {:?}", pkey.id()))
+        }
+    }
+
+    fn get_private_key(&self) -> anyhow::Result<&PKey<Private>> {
+        self.private_key
+            .as_ref()
+            .ok_or_else(|| anyhow!("no private key set"))
+    }
+
+    /// Build the algorithm identifier handed to the signing helper, e.g.
+    /// `SHA256_RSA2048`, from the optional digest and the public key type/size.
+    fn helper_algorithm_id(&self, digest: Option<KeylessSignDigest>) -> anyhow::Result<String> {
+        let prefix = digest.map(|d| format!("{}_", d.name())).unwrap_or_default();
+        match self.public_key.id() {
+            Id::RSA => {
+                let rsa = self
+                    .public_key
+                    .rsa()
+                    .map_err(|e| anyhow!("cert is not a valid rsa cert: {e}"))?;
+                Ok(format!("{prefix}RSA{}", rsa.size() as usize * 8))
+            }
+            Id::EC => Ok(format!("{prefix}EC")),
+            Id::ED25519 => Ok("ED25519".to_string()),
+            id => Err(anyhow!("unsupported public key type {id:?}")),
+        }
+    }
+
+    /// Delegate a private-key operation to the external signing helper.
+    ///
+    /// The helper is invoked with the algorithm identifier and the path to the
+    /// public key; `input` is written to its stdin and the raw signature is read
+    /// back from stdout.
+    fn run_signing_helper(&self, algorithm: &str, input: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let program = self
+            .signing_helper
+            .as_ref()
+            .ok_or_else(|| anyhow!("no signing helper set"))?;
+
+        let mut child = ProcessCommand::new(program)
+            .arg(algorithm)
+            .arg(&self.cert_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to spawn signing helper {}", program.display()))?;
+
+        child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow!("failed to open signing helper stdin"))?
+            .write_all(input)
+            .map_err(|e| anyhow!("failed to write payload to signing helper: {e}"))?;
+
+        let output = child
+            .wait_with_output()
+            .map_err(|e| anyhow!("failed to wait for signing helper: {e}"))?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "signing helper exited with status {}",
+                output.status
+            ));
+        }
+        Ok(output.stdout)
+    }
+
+    /// Check a signature produced by the helper against the local public key,
+    /// so a wrong key is caught immediately instead of later at the peer.
+    fn verify_helper_signature(
+        &self,
+        digest: KeylessSignDigest,
+        padding: Option<KeylessRsaPadding>,
+        signature: &[u8],
+    ) -> anyhow::Result<()> {
+        if self.check_signature(digest, padding, signature)? {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "signing helper returned a signature that does not match the public key"
+            ))
+        }
+    }
+
+    /// Run `Verifier` over the payload with the same digest and padding and
+    /// report whether `signature` matches `self.public_key`.
+    pub(super) fn check_signature(
+        &self,
+        digest: KeylessSignDigest,
+        padding: Option<KeylessRsaPadding>,
+        signature: &[u8],
+    ) -> anyhow::Result<bool> {
+        let mut verifier = Verifier::new(digest.into(), &self.public_key)
+            .map_err(|e| anyhow!("failed to create verifier: {e}"))?;
+        if let Some(padding) = padding {
+            verifier
+                .set_rsa_padding(padding.into())
+                .map_err(|e| anyhow!("failed to set rsa padding on verifier: {e}"))?;
+        }
+        verifier
+            .update(&self.payload)
+            .map_err(|e| anyhow!("failed to feed payload to verifier: {e}"))?;
+        verifier
+            .verify(signature)
+            .map_err(|e| anyhow!("signature verify error: {e}"))
+    }
+
+    pub(super) fn verify_enabled(&self) -> bool {
+        self.verify
+    }
+
+    /// Re-run the inverse of an encrypt/decrypt and check it reproduces the
+    /// original payload, turning the CLI into a correctness oracle.
+    pub(super) fn check_encrypt_roundtrip(
+        &self,
+        ciphertext: &[u8],
+        padding: Option<KeylessRsaPadding>,
+    ) -> anyhow::Result<bool> {
+        let mut decrypter = self.get_decrypter()?;
+        if let Some(padding) = padding {
+            decrypter
+                .set_rsa_padding(padding.into())
+                .map_err(|e| anyhow!("failed to set rsa padding: {e}"))?;
+        }
+        let buffer_len = decrypter
+            .decrypt_len(ciphertext)
+            .map_err(|e| anyhow!("failed to get buffer length: {e}"))?;
+        let mut decrypted = vec![0u8; buffer_len];
+        let len = decrypter
+            .decrypt(ciphertext, &mut decrypted)
+            .map_err(|e| anyhow!("failed to decrypt data: {e}"))?;
+        decrypted.truncate(len);
+        Ok(decrypted == self.payload)
+    }
+
+    fn use_signing_helper(&self) -> bool {
+        self.private_key.is_none() && self.signing_helper.is_some()
+    }
+
+    fn get_encrypter(&self) -> anyhow::Result<Encrypter> {
+        Encrypter::new(&self.public_key).map_err(|e| anyhow!("failed to create encrypter: {e}"))
+    }
+
+    pub(super) fn encrypt(&self) -> anyhow::Result<Vec<u8>> {
+        let encrypter = self.get_encrypter()?;
+        self.do_encrypt(encrypter)
+    }
+
+    pub(super) fn encrypt_rsa(&self, padding: KeylessRsaPadding) -> anyhow::Result<Vec<u8>> {
+        let mut encrypter = self.get_encrypter()?;
+        encrypter
+            .set_rsa_padding(padding.into())
+            .map_err(|e| anyhow!("failed to set rsa padding: {e}"))?;
+        self.do_encrypt(encrypter)
+    }
+
+    fn do_encrypt(&self, encrypter: Encrypter) -> anyhow::Result<Vec<u8>> {
+        let buffer_len = encrypter
+            .encrypt_len(&self.payload)
+            .map_err(|e| anyhow!("failed to get buffer length: {e}"))?;
+        let mut encrypted = vec![0u8; buffer_len];
+        let len = encrypter
+            .encrypt(&self.payload, &mut encrypted)
+            .map_err(|e| anyhow!("failed to encrypt data: {e}"))?;
+        encrypted.truncate(len);
+        Ok(encrypted)
+    }
+
+    fn get_decrypter(&self) -> anyhow::Result<Decrypter> {
+        let pkey = self.get_private_key()?;
+        Decrypter::new(pkey).map_err(|e| anyhow!("failed to create decrypter: {e}"))
+    }
+
+    pub(super) fn decrypt(&self) -> anyhow::Result<Vec<u8>> {
+        if self.use_signing_helper() {
+            let algorithm = self.helper_algorithm_id(None)?;
+            return self.run_signing_helper(&algorithm, &self.payload);
+        }
+        let decrypter = self.get_decrypter()?;
+        self.do_decrypt(decrypter)
+    }
+
+    pub(super) fn decrypt_rsa(&self, padding: KeylessRsaPadding) -> anyhow::Result<Vec<u8>> {
+        let mut decrypter = self.get_decrypter()?;
+        decrypter
+            .set_rsa_padding(padding.into())
+            .map_err(|e| anyhow!("failed to set rsa padding: {e}"))?;
+        self.do_decrypt(decrypter)
+    }
+
+    fn do_decrypt(&self, decrypter: Decrypter) -> anyhow::Result<Vec<u8>> {
+        let buffer_len = decrypter
+            .decrypt_len(&self.payload)
+            .map_err(|e| anyhow!("failed to get buffer length: {e}"))?;
+        let mut decrypted = vec![0u8; buffer_len];
+        let len = decrypter
+            .decrypt(&self.payload, &mut decrypted)
+            .map_err(|e| anyhow!("failed to decrypt data: {e}"))?;
+        decrypted.truncate(len);
+        Ok(decrypted)
+    }
+
+    pub(super) fn sign(&self, digest: KeylessSignDigest) -> anyhow::Result<Vec<u8>> {
+        if self.use_signing_helper() {
+            let algorithm = self.helper_algorithm_id(Some(digest))?;
+            let signature = self.run_signing_helper(&algorithm, &self.payload)?;
+            self.verify_helper_signature(digest, None, &signature)?;
+            return Ok(signature);
+        }
+        let pkey = self.get_private_key()?;
+        let signer = Signer::new(digest.into(), pkey)
+            .map_err(|e| anyhow!("error when create signer: {e}"))?;
+        self.do_sign(signer)
+    }
+
+    pub(super) fn sign_rsa(
+        &self,
+        digest: KeylessSignDigest,
+        padding: KeylessRsaPadding,
+    ) -> anyhow::Result<Vec<u8>> {
+        if self.use_signing_helper() {
+            let algorithm = self.helper_algorithm_id(Some(digest))?;
+            let signature = self.run_signing_helper(&algorithm, &self.payload)?;
+            self.verify_helper_signature(digest, Some(padding), &signature)?;
+            return Ok(signature);
+        }
+        let pkey = self.get_private_key()?;
+        let mut signer = Signer::new(digest.into(), pkey)
+            .map_err(|e| anyhow!("error when create signer: {e}"))?;
+        signer
+            .set_rsa_padding(padding.into())
+            .map_err(|e| anyhow!("failed to set rsa padding: {e}"))?;
+        if matches!(padding, KeylessRsaPadding::Pss) {
+            if let Some(salt_len) = self.pss_salt_len {
+                signer
+                    .set_rsa_pss_saltlen(salt_len)
+                    .map_err(|e| anyhow!("failed to set rsa pss salt length: {e}"))?;
+            }
+            if let Some(md) = self.mgf1_md {
+                signer
+                    .set_rsa_mgf1_md(md)
+                    .map_err(|e| anyhow!("failed to set rsa mgf1 digest: {e}"))?;
+            }
+        }
+        self.do_sign(signer)
+    }
+
+    pub(super) fn sign_ed(&self) -> anyhow::Result<Vec<u8>> {
+        let pkey = self.get_private_key()?;
+        let signer = Signer::new_without_digest(pkey)
+            .map_err(|e| anyhow!("error when create signer: {e}"))?;
+        self.do_sign(signer)
+    }
+
+    fn do_sign(&self, mut signer: Signer) -> anyhow::Result<Vec<u8>> {
+        signer
+            .update(&self.payload)
+            .map_err(|e| anyhow!("failed to set payload data: {e}"))?;
+        signer
+            .sign_to_vec()
+            .map_err(|e| anyhow!("sign failed: {e}"))
+    }
+
+    pub(super) fn rsa_private_encrypt(
+        &self,
+        padding: KeylessRsaPadding,
+    ) -> anyhow::Result<Vec<u8>> {
+        if self.use_signing_helper() {
+            let algorithm = self.helper_algorithm_id(None)?;
+            return self.run_signing_helper(&algorithm, &self.payload);
+        }
+        let pkey = self.get_private_key()?;
+        let rsa = pkey
+            .rsa()
+            .map_err(|e| anyhow!("private key is not rsa: {e}"))?;
+
+        let rsa_size = rsa.size() as usize;
+        let mut output_buf = vec![0u8; rsa_size];
+
+        let payload_len = self.payload.len();
+        if payload_len > rsa_size {
+            return Err(anyhow!(
+                "payload length {payload_len} is larger than RSA size {rsa_size}"
+            ));
+        }
+
+        let len = rsa
+            .private_decrypt(&self.payload, &mut output_buf, padding.into())
+            .map_err(|e| anyhow!("rsa private encrypt failed: {e}"))?;
+        output_buf.truncate(len);
+        Ok(output_buf)
+    }
+
+    /// The signer's half of the RFC 9474 blind-RSA protocol: a raw private
+    /// exponentiation `s' = b^d mod n` over the full modulus with no padding.
+    ///
+    /// The blinded input is already padded by the client, so it must be exactly
+    /// `rsa.size()` bytes and is exponentiated verbatim.
+    pub(super) fn rsa_blind_sign(&self) -> anyhow::Result<Vec<u8>> {
+        let pkey = self.get_private_key()?;
+        let rsa = pkey
+            .rsa()
+            .map_err(|e| anyhow!("private key is not rsa: {e}"))?;
+
+        let rsa_size = rsa.size() as usize;
+        let payload_len = self.payload.len();
+        if payload_len != rsa_size {
+            return Err(anyhow!(
+                "blinded input length {payload_len} is not equal to RSA size {rsa_size}"
+            ));
+        }
+
+        let mut output_buf = vec![0u8; rsa_size];
+        let len = rsa
+            .private_decrypt(&self.payload, &mut output_buf, Padding::NONE)
+            .map_err(|e| anyhow!("rsa blind sign failed: {e}"))?;
+        output_buf.truncate(len);
+        Ok(output_buf)
+    }
+
+    /// The client's half of the blind-RSA protocol, run end to end as a
+    /// self-test: pad the message, blind it, delegate the exponentiation (here
+    /// via the local private key), then unblind and confirm the result is a
+    /// signature that `Verifier` accepts.
+    ///
+    /// When `randomized` is set, the `message-randomized` variant of RFC 9474 is
+    /// used: 32 random bytes are prepended to the message before hashing and the
+    /// same randomized message is what the signature is produced over.
+    pub(super) fn rsa_blind_client(
+        &self,
+        digest: KeylessSignDigest,
+        padding: KeylessRsaPadding,
+        randomized: bool,
+    ) -> anyhow::Result<Vec<u8>> {
+        let rsa = self
+            .public_key
+            .rsa()
+            .map_err(|e| anyhow!("the cert is not a valid rsa cert: {e}"))?;
+        let n = rsa.n();
+        let e = rsa.e();
+
+        let mut ctx = BigNumContext::new_secure()
+            .map_err(|e| anyhow!("failed to create bignum context: {e}"))?;
+
+        // the message-randomized variant prepends a 32-byte randomizer
+        let mut input_msg = Vec::with_capacity(32 + self.payload.len());
+        if randomized {
+            let mut randomizer = [0u8; 32];
+            openssl::rand::rand_bytes(&mut randomizer)
+                .map_err(|e| anyhow!("failed to sample message randomizer: {e}"))?;
+            input_msg.extend_from_slice(&randomizer);
+        }
+        input_msg.extend_from_slice(&self.payload);
+
+        // (1) hash and pad the message to the encoded representative `m`
+        let m_bytes = self.pss_or_pkcs1_encode(digest, padding, &input_msg)?;
+        let m = BigNum::from_slice(&m_bytes)
+            .map_err(|e| anyhow!("failed to load padded message: {e}"))?;
+
+        // (2) sample a random `r` in [1, n) coprime to n and blind the message
+        let (r, r_inv) = self.sample_blinding_factor(n, &mut ctx)?;
+        let mut re = BigNum::new().map_err(|e| anyhow!("bignum alloc failed: {e}"))?;
+        re.mod_exp(&r, e, n, &mut ctx)
+            .map_err(|e| anyhow!("failed to compute r^e: {e}"))?;
+        let mut blinded = BigNum::new().map_err(|e| anyhow!("bignum alloc failed: {e}"))?;
+        blinded
+            .mod_mul(&m, &re, n, &mut ctx)
+            .map_err(|e| anyhow!("failed to blind message: {e}"))?;
+
+        // delegate the exponentiation; locally we reuse the private key
+        let d_pkey = self.get_private_key()?;
+        let d_rsa = d_pkey
+            .rsa()
+            .map_err(|e| anyhow!("private key is not rsa: {e}"))?;
+        let rsa_size = d_rsa.size() as usize;
+        let blinded_bytes = left_pad(&blinded.to_vec(), rsa_size);
+        let mut s_prime_buf = vec![0u8; rsa_size];
+        let len = d_rsa
+            .private_decrypt(&blinded_bytes, &mut s_prime_buf, Padding::NONE)
+            .map_err(|e| anyhow!("blind sign failed: {e}"))?;
+        s_prime_buf.truncate(len);
+        let s_prime = BigNum::from_slice(&s_prime_buf)
+            .map_err(|e| anyhow!("failed to load blind signature: {e}"))?;
+
+        // unblind: `s = s' * r^{-1} mod n`
+        let mut s = BigNum::new().map_err(|e| anyhow!("bignum alloc failed: {e}"))?;
+        s.mod_mul(&s_prime, &r_inv, n, &mut ctx)
+            .map_err(|e| anyhow!("failed to unblind signature: {e}"))?;
+
+        // verify `s^e mod n == m` as a cheap sanity check on the blinding math
+        let mut check = BigNum::new().map_err(|e| anyhow!("bignum alloc failed: {e}"))?;
+        check
+            .mod_exp(&s, e, n, &mut ctx)
+            .map_err(|e| anyhow!("failed to verify unblinded signature: {e}"))?;
+        if check != m {
+            return Err(anyhow!("unblinded signature failed representative check"));
+        }
+
+        // the real proof: the unblinded signature must verify against the public
+        // key as a genuine RSA signature over the (randomized) message.
+        let signature = left_pad(&s.to_vec(), rsa_size);
+        let md = MessageDigest::from(digest);
+        let mut verifier = Verifier::new(md, &self.public_key)
+            .map_err(|e| anyhow!("failed to create verifier: {e}"))?;
+        verifier
+            .set_rsa_padding(padding.into())
+            .map_err(|e| anyhow!("failed to set rsa padding on verifier: {e}"))?;
+        if matches!(padding, KeylessRsaPadding::Pss) {
+            verifier
+                .set_rsa_pss_saltlen(RsaPssSaltlen::DIGEST_LENGTH)
+                .map_err(|e| anyhow!("failed to set rsa pss salt length: {e}"))?;
+            verifier
+                .set_rsa_mgf1_md(md)
+                .map_err(|e| anyhow!("failed to set rsa mgf1 digest: {e}"))?;
+        }
+        verifier
+            .update(&input_msg)
+            .map_err(|e| anyhow!("failed to feed message to verifier: {e}"))?;
+        if !verifier
+            .verify(&signature)
+            .map_err(|e| anyhow!("signature verify error: {e}"))?
+        {
+            return Err(anyhow!("unblinded signature did not verify against public key"));
+        }
+
+        Ok(signature)
+    }
+
+    fn sample_blinding_factor(
+        &self,
+        n: &openssl::bn::BigNumRef,
+        ctx: &mut BigNumContext,
+    ) -> anyhow::Result<(BigNum, BigNum)> {
+        loop {
+            let mut r = BigNum::new().map_err(|e| anyhow!("bignum alloc failed: {e}"))?;
+            n.rand_range(&mut r)
+                .map_err(|e| anyhow!("failed to sample blinding factor: {e}"))?;
+            if r.is_zero() {
+                continue;
+            }
+            let mut r_inv = BigNum::new().map_err(|e| anyhow!("bignum alloc failed: {e}"))?;
+            // mod_inverse fails when gcd(r, n) != 1, i.e. r is not coprime to n
+            if r_inv.mod_inverse(&r, n, ctx).is_ok() {
+                return Ok((r, r_inv));
+            }
+        }
+    }
+
+    /// Produce the RSA encoded message representative `m` that the blinding
+    /// math operates on, applying the encoding selected by `padding` so that the
+    /// unblinded result is a genuine RSA signature.
+    fn pss_or_pkcs1_encode(
+        &self,
+        digest: KeylessSignDigest,
+        padding: KeylessRsaPadding,
+        message: &[u8],
+    ) -> anyhow::Result<Vec<u8>> {
+        let rsa = self
+            .public_key
+            .rsa()
+            .map_err(|e| anyhow!("the cert is not a valid rsa cert: {e}"))?;
+        let k = rsa.size() as usize;
+        let mod_bits = rsa.n().num_bits() as usize;
+        let md = MessageDigest::from(digest);
+        let m_hash = openssl::hash::hash(md, message)
+            .map_err(|e| anyhow!("failed to hash message: {e}"))?;
+
+        match padding {
+            KeylessRsaPadding::Pss => emsa_pss_encode(&m_hash, mod_bits, md, k),
+            KeylessRsaPadding::Pkcs1 => emsa_pkcs1_v1_5_encode(digest, &m_hash, k),
+            other => Err(anyhow!("unsupported padding {other:?} for blind-rsa")),
+        }
+    }
+
+    /// Derive a shared secret from the loaded private key and the peer's
+    /// ephemeral public point (hex, in the payload field), as required by a
+    /// keyless TLS 1.3 ECDHE or static-ECDH key exchange.
+    pub(super) fn ecdh(&self) -> anyhow::Result<Vec<u8>> {
+        let pkey = self.get_private_key()?;
+        let ec = self
+            .public_key
+            .ec_key()
+            .map_err(|e| anyhow!("the cert is not a valid ec cert: {e}"))?;
+        let group = ec.group();
+
+        let mut ctx = BigNumContext::new_secure()
+            .map_err(|e| anyhow!("failed to create bignum context: {e}"))?;
+        let point = EcPoint::from_bytes(group, &self.payload, &mut ctx)
+            .map_err(|e| anyhow!("invalid peer public point: {e}"))?;
+        let peer_ec = EcKey::from_public_key(group, &point)
+            .map_err(|e| anyhow!("failed to build peer ec key: {e}"))?;
+        let peer = PKey::from_ec_key(peer_ec)
+            .map_err(|e| anyhow!("failed to wrap peer ec key: {e}"))?;
+
+        let mut deriver =
+            Deriver::new(pkey).map_err(|e| anyhow!("failed to create deriver: {e}"))?;
+        deriver
+            .set_peer(&peer)
+            .map_err(|e| anyhow!("failed to set ecdh peer: {e}"))?;
+        deriver
+            .derive_to_vec()
+            .map_err(|e| anyhow!("ecdh derive failed: {e}"))
+    }
+
+    pub(super) fn rsa_public_decrypt(&self, padding: KeylessRsaPadding) -> anyhow::Result<Vec<u8>> {
+        let rsa = self
+            .public_key
+            .rsa()
+            .map_err(|e| anyhow!("the cert is not a valid rsa cert: {e}"))?;
+
+        let rsa_size = rsa.size() as usize;
+        let mut output_buf = vec![0u8; rsa_size];
+
+        let payload_len = self.payload.len();
+        if payload_len != rsa_size {
+            return Err(anyhow!(
+                "payload length {payload_len} is not equal to RSA size {rsa_size}"
+            ));
+        }
+
+        let len = rsa
+            .public_decrypt(&self.payload, &mut output_buf, padding.into())
+            .map_err(|e| anyhow!("rsa public decrypt failed: {e}"))?;
+        output_buf.truncate(len);
+        Ok(output_buf)
+    }
+}
+
+/// Assemble a `PKey<Private>` from its individual components, supplied as a JSON
+/// object of hex-encoded values.
+///
+/// RSA keys carry `n, e, d` and optionally the CRT parameters
+/// `p, q, dmp1, dmq1, iqmp`. EC keys carry `crv`, the scalar `d` and the public
+/// point `x, y`. Ed25519 keys carry `crv: "Ed25519"` and the raw scalar `d`.
+fn load_key_components(path: &PathBuf) -> anyhow::Result<PKey<Private>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read key components file {}", path.display()))?;
+    let value: Value = serde_json::from_str(&content)
+        .map_err(|e| anyhow!("invalid key components json: {e}"))?;
+
+    match value.get("crv").and_then(Value::as_str) {
+        Some("Ed25519") => return load_ed25519_components(&value),
+        Some(crv) => return load_ec_components(&value, crv),
+        None => {}
+    }
+
+    let hex_bn = |name: &str| -> anyhow::Result<BigNum> {
+        let s = value
+            .get(name)
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("missing rsa component {name}"))?;
+        BigNum::from_hex_str(s).map_err(|e| anyhow!("invalid rsa component {name}: {e}"))
+    };
+    let opt_hex_bn = |name: &str| -> anyhow::Result<Option<BigNum>> {
+        match value.get(name).and_then(Value::as_str) {
+            Some(s) => Ok(Some(
+                BigNum::from_hex_str(s).map_err(|e| anyhow!("invalid rsa component {name}: {e}"))?,
+            )),
+            None => Ok(None),
+        }
+    };
+
+    let mut builder = RsaPrivateKeyBuilder::new(hex_bn("n")?, hex_bn("e")?, hex_bn("d")?)
+        .map_err(|e| anyhow!("failed to set rsa n/e/d: {e}"))?;
+    if let (Some(p), Some(q)) = (opt_hex_bn("p")?, opt_hex_bn("q")?) {
+        builder = builder
+            .set_factors(p, q)
+            .map_err(|e| anyhow!("failed to set rsa factors: {e}"))?;
+    }
+    if let (Some(dmp1), Some(dmq1), Some(iqmp)) =
+        (opt_hex_bn("dmp1")?, opt_hex_bn("dmq1")?, opt_hex_bn("iqmp")?)
+    {
+        builder = builder
+            .set_crt_params(dmp1, dmq1, iqmp)
+            .map_err(|e| anyhow!("failed to set rsa crt params: {e}"))?;
+    }
+    let rsa = builder.build();
+    PKey::from_rsa(rsa).map_err(|e| anyhow!("failed to build rsa private key: {e}"))
+}
+
+fn load_ec_components(value: &Value, crv: &str) -> anyhow::Result<PKey<Private>> {
+    let hex_bn = |name: &str| -> anyhow::Result<BigNum> {
+        let s = value
+            .get(name)
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("missing ec component {name}"))?;
+        BigNum::from_hex_str(s).map_err(|e| anyhow!("invalid ec component {name}: {e}"))
+    };
+
+    let group =
+        EcGroup::from_curve_name(jwk_ec_nid(crv)?).map_err(|e| anyhow!("failed to load ec group: {e}"))?;
+    let x = hex_bn("x")?;
+    let y = hex_bn("y")?;
+    let d = hex_bn("d")?;
+    let pub_key = EcKey::from_public_key_affine_coordinates(&group, &x, &y)
+        .map_err(|e| anyhow!("invalid ec public point: {e}"))?;
+    let ec = EcKey::from_private_components(&group, &d, pub_key.public_key())
+        .map_err(|e| anyhow!("failed to build ec private key: {e}"))?;
+    PKey::from_ec_key(ec).map_err(|e| anyhow!("failed to wrap ec private key: {e}"))
+}
+
+fn load_ed25519_components(value: &Value) -> anyhow::Result<PKey<Private>> {
+    let s = value
+        .get("d")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("missing ed25519 component d"))?;
+    let d = hex::decode(s).map_err(|e| anyhow!("invalid ed25519 component d: {e}"))?;
+    PKey::private_key_from_raw_bytes(&d, Id::ED25519)
+        .map_err(|e| anyhow!("failed to build ed25519 private key: {e}"))
+}
+
+/// Assemble a `PKey<Private>` from a JWK, covering RSA (`kty=RSA`), EC
+/// (`kty=EC`) and Ed25519 (`kty=OKP`). All parameters are base64url-encoded as
+/// defined by RFC 7518.
+fn load_key_jwk(path: &PathBuf) -> anyhow::Result<PKey<Private>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read jwk file {}", path.display()))?;
+    let value: Value = serde_json::from_str(&content).map_err(|e| anyhow!("invalid jwk: {e}"))?;
+
+    let b64_bn = |name: &str| -> anyhow::Result<BigNum> {
+        BigNum::from_slice(&jwk_b64(&value, name)?)
+            .map_err(|e| anyhow!("invalid jwk field {name}: {e}"))
+    };
+
+    match value.get("kty").and_then(Value::as_str) {
+        Some("RSA") => {
+            let mut builder = RsaPrivateKeyBuilder::new(b64_bn("n")?, b64_bn("e")?, b64_bn("d")?)
+                .map_err(|e| anyhow!("failed to set rsa n/e/d: {e}"))?;
+            if value.get("p").is_some() && value.get("q").is_some() {
+                builder = builder
+                    .set_factors(b64_bn("p")?, b64_bn("q")?)
+                    .map_err(|e| anyhow!("failed to set rsa factors: {e}"))?;
+            }
+            if value.get("dp").is_some() && value.get("dq").is_some() && value.get("qi").is_some() {
+                builder = builder
+                    .set_crt_params(b64_bn("dp")?, b64_bn("dq")?, b64_bn("qi")?)
+                    .map_err(|e| anyhow!("failed to set rsa crt params: {e}"))?;
+            }
+            PKey::from_rsa(builder.build())
+                .map_err(|e| anyhow!("failed to build rsa private key: {e}"))
+        }
+        Some("EC") => {
+            let crv = value
+                .get("crv")
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow!("missing jwk crv"))?;
+            let group = EcGroup::from_curve_name(jwk_ec_nid(crv)?)
+                .map_err(|e| anyhow!("failed to load ec group: {e}"))?;
+            let x = b64_bn("x")?;
+            let y = b64_bn("y")?;
+            let d = b64_bn("d")?;
+            let pub_key = EcKey::from_public_key_affine_coordinates(&group, &x, &y)
+                .map_err(|e| anyhow!("invalid ec public point: {e}"))?;
+            let ec = EcKey::from_private_components(&group, &d, pub_key.public_key())
+                .map_err(|e| anyhow!("failed to build ec private key: {e}"))?;
+            PKey::from_ec_key(ec).map_err(|e| anyhow!("failed to wrap ec private key: {e}"))
+        }
+        Some("OKP") => {
+            let d = jwk_b64(&value, "d")?;
+            PKey::private_key_from_raw_bytes(&d, Id::ED25519)
+                .map_err(|e| anyhow!("failed to build ed25519 private key: {e}"))
+        }
+        other => Err(anyhow!("unsupported jwk key type {other:?}")),
+    }
+}
+
+fn jwk_b64(value: &Value, name: &str) -> anyhow::Result<Vec<u8>> {
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+
+    let s = value
+        .get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("missing jwk field {name}"))?;
+    URL_SAFE_NO_PAD
+        .decode(s)
+        .map_err(|e| anyhow!("invalid base64url in jwk field {name}: {e}"))
+}
+
+fn jwk_ec_nid(crv: &str) -> anyhow::Result<Nid> {
+    match crv {
+        "P-256" => Ok(Nid::X9_62_PRIME256V1),
+        "P-384" => Ok(Nid::SECP384R1),
+        "P-521" => Ok(Nid::SECP521R1),
+        _ => Err(anyhow!("unsupported jwk ec curve {crv}")),
+    }
+}
+
+fn parse_pss_salt_len(s: &str) -> anyhow::Result<RsaPssSaltlen> {
+    match s.to_lowercase().as_str() {
+        "digest" => Ok(RsaPssSaltlen::DIGEST_LENGTH),
+        "max" => Ok(RsaPssSaltlen::MAXIMUM_LENGTH),
+        _ => {
+            let n = i32::from_str(s)
+                .map_err(|_| anyhow!("invalid pss salt length {s}, expected N|digest|max"))?;
+            Ok(RsaPssSaltlen::custom(n))
+        }
+    }
+}
+
+/// The MGF1 mask generation function of RFC 8017, built on `md`.
+fn mgf1(seed: &[u8], len: usize, md: MessageDigest) -> anyhow::Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(len);
+    let mut counter: u32 = 0;
+    while out.len() < len {
+        let mut block = Vec::with_capacity(seed.len() + 4);
+        block.extend_from_slice(seed);
+        block.extend_from_slice(&counter.to_be_bytes());
+        let digest =
+            openssl::hash::hash(md, &block).map_err(|e| anyhow!("mgf1 hash error: {e}"))?;
+        out.extend_from_slice(&digest);
+        counter += 1;
+    }
+    out.truncate(len);
+    Ok(out)
+}
+
+/// EMSA-PSS encoding (RFC 8017 §9.1.1) with a salt length equal to the digest
+/// length, returning the `k`-byte encoded message representative.
+fn emsa_pss_encode(
+    m_hash: &[u8],
+    mod_bits: usize,
+    md: MessageDigest,
+    k: usize,
+) -> anyhow::Result<Vec<u8>> {
+    let h_len = m_hash.len();
+    let s_len = h_len;
+    let em_bits = mod_bits - 1;
+    let em_len = em_bits.div_ceil(8);
+    if em_len < h_len + s_len + 2 {
+        return Err(anyhow!("rsa key too small for pss encoding"));
+    }
+
+    let mut salt = vec![0u8; s_len];
+    openssl::rand::rand_bytes(&mut salt).map_err(|e| anyhow!("failed to sample pss salt: {e}"))?;
+
+    let mut m_prime = Vec::with_capacity(8 + h_len + s_len);
+    m_prime.extend_from_slice(&[0u8; 8]);
+    m_prime.extend_from_slice(m_hash);
+    m_prime.extend_from_slice(&salt);
+    let h = openssl::hash::hash(md, &m_prime).map_err(|e| anyhow!("pss hash error: {e}"))?;
+
+    // DB = PS || 0x01 || salt, of length em_len - h_len - 1
+    let db_len = em_len - h_len - 1;
+    let mut db = vec![0u8; db_len];
+    db[db_len - s_len - 1] = 0x01;
+    db[db_len - s_len..].copy_from_slice(&salt);
+
+    let db_mask = mgf1(&h, db_len, md)?;
+    for (b, m) in db.iter_mut().zip(db_mask.iter()) {
+        *b ^= *m;
+    }
+    // clear the leftmost 8*em_len - em_bits bits of the masked DB
+    let clear_bits = 8 * em_len - em_bits;
+    db[0] &= 0xff >> clear_bits;
+
+    let mut em = Vec::with_capacity(em_len);
+    em.extend_from_slice(&db);
+    em.extend_from_slice(&h);
+    em.push(0xbc);
+
+    Ok(left_pad(&em, k))
+}
+
+/// The DER `DigestInfo` prefix prepended to the hash in EMSA-PKCS1-v1_5.
+fn pkcs1_digest_info_prefix(digest: KeylessSignDigest) -> anyhow::Result<&'static [u8]> {
+    match digest {
+        KeylessSignDigest::Sha1 => Ok(&[
+            0x30, 0x21, 0x30, 0x09, 0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a, 0x05, 0x00, 0x04,
+            0x14,
+        ]),
+        KeylessSignDigest::Sha224 => Ok(&[
+            0x30, 0x2d, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x04, 0x05, 0x00, 0x04, 0x1c,
+        ]),
+        KeylessSignDigest::Sha256 => Ok(&[
+            0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x01, 0x05, 0x00, 0x04, 0x20,
+        ]),
+        KeylessSignDigest::Sha384 => Ok(&[
+            0x30, 0x41, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x02, 0x05, 0x00, 0x04, 0x30,
+        ]),
+        KeylessSignDigest::Sha512 => Ok(&[
+            0x30, 0x51, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
+            0x03, 0x05, 0x00, 0x04, 0x40,
+        ]),
+        KeylessSignDigest::Md5Sha1 => Err(anyhow!("md5sha1 has no pkcs1 DigestInfo encoding")),
+    }
+}
+
+/// EMSA-PKCS1-v1_5 encoding (RFC 8017 §9.2) returning the `k`-byte encoded
+/// message representative.
+fn emsa_pkcs1_v1_5_encode(
+    digest: KeylessSignDigest,
+    m_hash: &[u8],
+    k: usize,
+) -> anyhow::Result<Vec<u8>> {
+    let prefix = pkcs1_digest_info_prefix(digest)?;
+    let t_len = prefix.len() + m_hash.len();
+    if k < t_len + 11 {
+        return Err(anyhow!("rsa key too small for pkcs1 encoding"));
+    }
+
+    let mut em = Vec::with_capacity(k);
+    em.push(0x00);
+    em.push(0x01);
+    em.extend(std::iter::repeat(0xff).take(k - t_len - 3));
+    em.push(0x00);
+    em.extend_from_slice(prefix);
+    em.extend_from_slice(m_hash);
+    Ok(em)
+}
+
+/// Left-pad `data` with zero bytes to `len`, as RSA integers expect a
+/// fixed-width big-endian representation.
+fn left_pad(data: &[u8], len: usize) -> Vec<u8> {
+    if data.len() >= len {
+        return data.to_vec();
+    }
+    let mut out = vec![0u8; len - data.len()];
+    out.extend_from_slice(data);
+    out
+}
+
+fn add_keyless_args(cmd: Command) -> Command {
+    cmd.arg(
+        Arg::new(ARG_CERT)
+            .help("Target certificate file")
+            .num_args(1)
+            .long(ARG_CERT)
+            .value_parser(value_parser!(PathBuf))
+            .required(true)
+            .value_hint(ValueHint::FilePath),
+    )
+    .arg(
+        Arg::new(ARG_PKEY)
+            .help("Target private key file")
+            .num_args(1)
+            .long(ARG_PKEY)
+            .value_parser(value_parser!(PathBuf))
+            .value_hint(ValueHint::FilePath),
+    )
+    .arg(
+        Arg::new(ARG_KEY_COMPONENTS)
+            .help("Load the private key from raw RSA/EC/Ed25519 components (JSON of hex values)")
+            .num_args(1)
+            .long(ARG_KEY_COMPONENTS)
+            .value_parser(value_parser!(PathBuf))
+            .conflicts_with(ARG_PKEY)
+            .value_hint(ValueHint::FilePath),
+    )
+    .arg(
+        Arg::new(ARG_KEY_JWK)
+            .help("Load the private key from a JWK file")
+            .num_args(1)
+            .long(ARG_KEY_JWK)
+            .value_parser(value_parser!(PathBuf))
+            .conflicts_with(ARG_PKEY)
+            .conflicts_with(ARG_KEY_COMPONENTS)
+            .value_hint(ValueHint::FilePath),
+    )
+    .arg(
+        Arg::new(ARG_SIGNING_HELPER)
+            .help("Delegate private-key operations to an external signing helper program")
+            .num_args(1)
+            .long(ARG_SIGNING_HELPER)
+            .value_parser(value_parser!(PathBuf))
+            .conflicts_with(ARG_PKEY)
+            .value_hint(ValueHint::FilePath),
+    )
+    .arg(
+        Arg::new(ARG_SIGN)
+            .help("Computes cryptographic signatures of data")
+            .num_args(0)
+            .long(ARG_SIGN)
+            .action(ArgAction::SetTrue)
+            .requires(ARG_DIGEST_TYPE),
+    )
+    .arg(
+        Arg::new(ARG_DECRYPT)
+            .help("Decrypt data with the corresponding private key")
+            .num_args(0)
+            .long(ARG_DECRYPT)
+            .action(ArgAction::SetTrue),
+    )
+    .arg(
+        Arg::new(ARG_ENCRYPT)
+            .help("Encrypt data with the corresponding public key")
+            .num_args(0)
+            .long(ARG_ENCRYPT)
+            .action(ArgAction::SetTrue),
+    )
+    .arg(
+        Arg::new(ARG_RSA_PRIVATE_ENCRYPT)
+            .help("RSA Private Encrypt")
+            .num_args(0)
+            .long(ARG_RSA_PRIVATE_ENCRYPT)
+            .action(ArgAction::SetTrue)
+            .requires(ARG_RSA_PADDING),
+    )
+    .arg(
+        Arg::new(ARG_RSA_PUBLIC_DECRYPT)
+            .help("RSA Public Decrypt")
+            .num_args(0)
+            .long(ARG_RSA_PUBLIC_DECRYPT)
+            .action(ArgAction::SetTrue)
+            .requires(ARG_RSA_PADDING),
+    )
+    .arg(
+        Arg::new(ARG_BLIND_SIGN)
+            .help("Signer half of the RFC 9474 blind-RSA protocol")
+            .num_args(0)
+            .long(ARG_BLIND_SIGN)
+            .action(ArgAction::SetTrue),
+    )
+    .arg(
+        Arg::new(ARG_BLIND_CLIENT)
+            .help("Client half of the RFC 9474 blind-RSA protocol (self-test)")
+            .num_args(0)
+            .long(ARG_BLIND_CLIENT)
+            .action(ArgAction::SetTrue)
+            .requires(ARG_DIGEST_TYPE),
+    )
+    .arg(
+        Arg::new(ARG_BLIND_RANDOMIZED)
+            .help("Use the message-randomized variant of the blind-RSA client")
+            .num_args(0)
+            .long(ARG_BLIND_RANDOMIZED)
+            .action(ArgAction::SetTrue)
+            .requires(ARG_BLIND_CLIENT),
+    )
+    .arg(
+        Arg::new(ARG_ECDH)
+            .help("Derive an ECDH shared secret with the peer public point")
+            .num_args(0)
+            .long(ARG_ECDH)
+            .action(ArgAction::SetTrue),
+    )
+    .group(
+        ArgGroup::new("method")
+            .args([
+                ARG_SIGN,
+                ARG_DECRYPT,
+                ARG_ENCRYPT,
+                ARG_RSA_PRIVATE_ENCRYPT,
+                ARG_RSA_PUBLIC_DECRYPT,
+                ARG_BLIND_SIGN,
+                ARG_BLIND_CLIENT,
+                ARG_ECDH,
+            ])
+            .required(true),
+    )
+    .arg(
+        Arg::new(ARG_DIGEST_TYPE)
+            .help("Sign Digest Type")
+            .num_args(1)
+            .long(ARG_DIGEST_TYPE)
+            .value_parser(DIGEST_TYPES),
+    )
+    .arg(
+        Arg::new(ARG_RSA_PADDING)
+            .help("RSA Padding Type")
+            .num_args(1)
+            .long(ARG_RSA_PADDING)
+            .value_parser(RSA_PADDING_VALUES)
+            .default_value("PKCS1"),
+    )
+    .arg(
+        Arg::new(ARG_PSS_SALT_LEN)
+            .help("RSA-PSS salt length (a byte count, or \"digest\"/\"max\")")
+            .num_args(1)
+            .long(ARG_PSS_SALT_LEN),
+    )
+    .arg(
+        Arg::new(ARG_MGF1_DIGEST)
+            .help("RSA-PSS MGF1 digest type")
+            .num_args(1)
+            .long(ARG_MGF1_DIGEST)
+            .value_parser(DIGEST_TYPES),
+    )
+    .arg(
+        Arg::new(ARG_PAYLOAD)
+            .help("Payload data")
+            .num_args(1)
+            .required(true),
+    )
+    .arg(
+        Arg::new(ARG_VERIFY)
+            .help("Self-check the produced signature or ciphertext and report pass/fail")
+            .action(ArgAction::SetTrue)
+            .num_args(0)
+            .long(ARG_VERIFY),
+    )
+    .arg(
+        Arg::new(ARG_DUMP_RESULT)
+            .help("Dump output use hex string")
+            .action(ArgAction::SetTrue)
+            .num_args(0)
+            .long(ARG_DUMP_RESULT),
+    )
+}
+
+impl AppendKeylessArgs for Command {
+    fn append_keyless_args(self) -> Self {
+        add_keyless_args(self)
+    }
+}
@@ -0,0 +1,37 @@
+/*
+ * Copyright 2023 ByteDance and/or its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use g3_dpi::{ProtocolInspectionConfig, ProtocolInspector};
+
+fuzz_target!(|data: &[u8]| {
+    // derive a port from the leading byte pair so the corpus explores the
+    // port-based branches too, then feed the rest as the client's bytes.
+    let (port, bytes) = match data.split_first_chunk::<2>() {
+        Some((p, rest)) => (u16::from_be_bytes(*p), rest),
+        None => (0, data),
+    };
+
+    let mut inspector = ProtocolInspector::default();
+    let config = ProtocolInspectionConfig::default();
+
+    // must never panic and must always terminate with either a classification
+    // or an explicit "need more data" error.
+    let _ = inspector.check_client_initial_data(&config, port, bytes);
+});
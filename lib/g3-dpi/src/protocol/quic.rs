@@ -0,0 +1,72 @@
+/*
+ * Copyright 2023 ByteDance and/or its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::Protocol;
+
+const HEADER_FORM_LONG: u8 = 0x80;
+const FIXED_BIT: u8 = 0x40;
+
+const CID_LEN_MAX: u8 = 20;
+
+const VERSION_NEGOTIATION: u32 = 0x0000_0000;
+const VERSION_V1: u32 = 0x0000_0001;
+const VERSION_V2: u32 = 0x6b33_43cf;
+
+fn is_known_version(version: u32) -> bool {
+    match version {
+        VERSION_NEGOTIATION | VERSION_V1 | VERSION_V2 => true,
+        // the draft-ietf-quic-transport series all share the 0xff0000xx space
+        v => (0xff00_0000..=0xff00_0022).contains(&v),
+    }
+}
+
+/// Inspect the first UDP datagram a client sends and tell whether it is the
+/// Initial packet of a QUIC connection.
+///
+/// This only looks at the invariant long-header fields defined in RFC 8999 so
+/// that it can tell a plausible Initial packet apart from random UDP noise
+/// without attempting to decrypt the payload.
+pub(crate) fn check_client_initial_data(data: &[u8]) -> Option<Protocol> {
+    // 1 flags byte + 4 version bytes + 1 DCID length byte
+    if data.len() < 6 {
+        return None;
+    }
+
+    let flags = data[0];
+    if (flags & HEADER_FORM_LONG) == 0 || (flags & FIXED_BIT) == 0 {
+        return None;
+    }
+
+    let version = u32::from_be_bytes([data[1], data[2], data[3], data[4]]);
+    if !is_known_version(version) {
+        return None;
+    }
+
+    let dcid_len = data[5];
+    if dcid_len > CID_LEN_MAX {
+        return None;
+    }
+
+    let scid_len_offset = 6 + dcid_len as usize;
+    let Some(&scid_len) = data.get(scid_len_offset) else {
+        return None;
+    };
+    if scid_len > CID_LEN_MAX {
+        return None;
+    }
+
+    Some(Protocol::Quic)
+}
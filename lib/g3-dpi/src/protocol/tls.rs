@@ -0,0 +1,171 @@
+/*
+ * Copyright 2023 ByteDance and/or its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+const RECORD_TYPE_HANDSHAKE: u8 = 22;
+const HANDSHAKE_TYPE_CLIENT_HELLO: u8 = 1;
+
+const EXT_SERVER_NAME: u16 = 0x0000;
+const EXT_SUPPORTED_VERSIONS: u16 = 0x002b;
+const EXT_ENCRYPTED_CLIENT_HELLO: u16 = 0xfe0d;
+
+const SNI_TYPE_HOST_NAME: u8 = 0x00;
+
+/// The parsed fields of interest from a TLS ClientHello.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TlsClientHello {
+    pub sni: Option<String>,
+    pub ech_present: bool,
+    pub supported_versions: bool,
+}
+
+/// Outcome of looking at the client's initial bytes as a TLS record.
+pub(crate) enum TlsInspectStatus {
+    /// The bytes are not a TLS handshake record.
+    NotTls,
+    /// The record header is plausible but the buffered bytes do not yet cover
+    /// the whole ClientHello; the caller should read more before deciding.
+    NeedMoreData,
+    /// A ClientHello was parsed successfully.
+    ClientHello(TlsClientHello),
+}
+
+/// A forward-only cursor that reports truncation instead of panicking.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Reader { data, pos: 0 }
+    }
+
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(n)?;
+        let s = self.data.get(self.pos..end)?;
+        self.pos = end;
+        Some(s)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|s| s[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|s| u16::from_be_bytes([s[0], s[1]]))
+    }
+
+    /// Skip a variable-length vector whose length is carried in `len_bytes`.
+    fn skip_vec(&mut self, len_bytes: usize) -> Option<()> {
+        let len = match len_bytes {
+            1 => self.u8()? as usize,
+            2 => self.u16()? as usize,
+            _ => return None,
+        };
+        self.take(len).map(|_| ())
+    }
+}
+
+/// Inspect the client's initial bytes as a TLS ClientHello.
+pub(crate) fn check_client_initial_data(data: &[u8]) -> TlsInspectStatus {
+    // TLS record header: type(1) + legacy_version(2) + length(2)
+    let Some(header) = data.get(0..5) else {
+        return TlsInspectStatus::NeedMoreData;
+    };
+    if header[0] != RECORD_TYPE_HANDSHAKE || header[1] != 0x03 {
+        return TlsInspectStatus::NotTls;
+    }
+
+    let record_len = u16::from_be_bytes([header[3], header[4]]) as usize;
+    let Some(body) = data.get(5..) else {
+        return TlsInspectStatus::NeedMoreData;
+    };
+    if body.len() < record_len {
+        // the ClientHello spans more than what is buffered so far
+        return TlsInspectStatus::NeedMoreData;
+    }
+
+    match parse_client_hello(&body[..record_len]) {
+        Some(Some(hello)) => TlsInspectStatus::ClientHello(hello),
+        Some(None) => TlsInspectStatus::NeedMoreData,
+        None => TlsInspectStatus::NotTls,
+    }
+}
+
+/// Parse the handshake body.
+///
+/// Returns `None` when the bytes are not a well-formed ClientHello, `Some(None)`
+/// when they are truncated, and `Some(Some(_))` on success.
+fn parse_client_hello(body: &[u8]) -> Option<Option<TlsClientHello>> {
+    let mut r = Reader::new(body);
+
+    if r.u8()? != HANDSHAKE_TYPE_CLIENT_HELLO {
+        return Some(None);
+    }
+    // handshake length (3 bytes)
+    let hs_len = {
+        let b = r.take(3)?;
+        ((b[0] as usize) << 16) | ((b[1] as usize) << 8) | (b[2] as usize)
+    };
+    let hs = Reader::new(r.take(hs_len)?);
+    let mut r = hs;
+
+    r.take(2)?; // legacy client_version
+    r.take(32)?; // random
+    r.skip_vec(1)?; // session_id
+    r.skip_vec(2)?; // cipher_suites
+    r.skip_vec(1)?; // compression_methods
+
+    let ext_total = match r.u16() {
+        Some(len) => len as usize,
+        // a ClientHello with no extensions list carries no SNI/ECH
+        None => return Some(Some(TlsClientHello::default())),
+    };
+    let ext_bytes = r.take(ext_total)?;
+
+    let mut hello = TlsClientHello::default();
+    let mut er = Reader::new(ext_bytes);
+    while er.pos < ext_bytes.len() {
+        let ext_type = er.u16()?;
+        let ext_len = er.u16()? as usize;
+        let ext_data = er.take(ext_len)?;
+        match ext_type {
+            EXT_SERVER_NAME => hello.sni = parse_sni(ext_data),
+            EXT_SUPPORTED_VERSIONS => hello.supported_versions = true,
+            EXT_ENCRYPTED_CLIENT_HELLO => hello.ech_present = true,
+            _ => {}
+        }
+    }
+
+    Some(Some(hello))
+}
+
+fn parse_sni(data: &[u8]) -> Option<String> {
+    let mut r = Reader::new(data);
+    let list_len = r.u16()? as usize;
+    let list = r.take(list_len)?;
+
+    let mut lr = Reader::new(list);
+    while lr.pos < list.len() {
+        let name_type = lr.u8()?;
+        let name_len = lr.u16()? as usize;
+        let name = lr.take(name_len)?;
+        if name_type == SNI_TYPE_HOST_NAME {
+            return std::str::from_utf8(name).ok().map(str::to_string);
+        }
+    }
+    None
+}
@@ -0,0 +1,35 @@
+/*
+ * Copyright 2023 ByteDance and/or its affiliates.
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use g3_xcrypt::XCrypt;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(s) = std::str::from_utf8(data) else {
+        return;
+    };
+    // the parse dispatch does a lot of byte slicing around `$` delimiters and
+    // `rounds=`; it must never panic on arbitrary input.
+    if let Ok(crypt) = XCrypt::parse(s) {
+        // any value that parsed must round-trip through verify without slicing
+        // out of bounds, whatever the phrase.
+        let _ = crypt.verify(b"");
+        let _ = crypt.verify(data);
+    }
+});
@@ -14,15 +14,21 @@
  * limitations under the License.
  */
 
+use std::fmt;
 use std::str::FromStr;
 
 use digest::{Digest, Output};
+use rand::Rng;
 use sha2::Sha256;
 
 use super::{B64CryptEncoder, XCryptParseError, XCryptParseResult};
 
 pub(super) const PREFIX: &str = "$5$";
 
+/// The base64 alphabet used by the crypt salt, in glibc order.
+const SALT_CHARS: &[u8; 64] =
+    b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
 const SALT_LEN_MAX: usize = 16;
 
 const ROUNDS_DEFAULT: usize = 5000;
@@ -159,6 +165,26 @@ fn do_sha256_hash(phrase: &[u8], salt: &str, rounds: usize) -> String {
 }
 
 impl Sha256Crypt {
+    /// Generate a fresh `$5$` hash for `phrase`.
+    ///
+    /// An explicit round count may be given; when omitted the glibc default is
+    /// used. A random 16-character salt is drawn from `rng`, which is taken as a
+    /// parameter so callers (and tests) can supply a deterministic source.
+    pub fn new<R: Rng + ?Sized>(phrase: &[u8], rounds: Option<usize>, rng: &mut R) -> Self {
+        let rounds = rounds
+            .map(|r| r.clamp(ROUNDS_MIN, ROUNDS_MAX))
+            .unwrap_or(ROUNDS_DEFAULT);
+        let salt: String = (0..SALT_LEN_MAX)
+            .map(|_| SALT_CHARS[rng.gen_range(0..SALT_CHARS.len())] as char)
+            .collect();
+        let hash = do_sha256_hash(phrase, &salt, rounds);
+        Sha256Crypt {
+            rounds,
+            salt,
+            hash,
+        }
+    }
+
     pub(super) fn parse(v: &str) -> XCryptParseResult<Self> {
         let mut rounds = ROUNDS_DEFAULT;
         let mut s = v;
@@ -169,9 +195,7 @@ impl Sha256Crypt {
                 }
 
                 rounds = usize::from_str(&r[0..d]).map_err(|_| XCryptParseError::InvalidRounds)?;
-                if (ROUNDS_MIN..=ROUNDS_MAX).contains(&rounds) {
-                    return Err(XCryptParseError::OutOfRangeRounds);
-                }
+                rounds = rounds.clamp(ROUNDS_MIN, ROUNDS_MAX);
 
                 s = &r[d + 1..];
             } else {
@@ -206,3 +230,41 @@ impl Sha256Crypt {
         self.hash.eq(&hash)
     }
 }
+
+impl fmt::Display for Sha256Crypt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(PREFIX)?;
+        if self.rounds != ROUNDS_DEFAULT {
+            write!(f, "rounds={}$", self.rounds)?;
+        }
+        write!(f, "{}${}", self.salt, self.hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn verify_known_hash() {
+        // `openssl passwd -5 -salt saltstring "Hello world!"`
+        let crypt =
+            Sha256Crypt::parse("saltstring$5B8vYYiY.CVt1RlTTf8KbXBH3hsxY/GNooZaBBGWEc5").unwrap();
+        assert!(crypt.verify(b"Hello world!"));
+        assert!(!crypt.verify(b"wrong password"));
+    }
+
+    #[test]
+    fn generate_round_trip() {
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+        let crypt = Sha256Crypt::new(b"rotate-me", None, &mut rng);
+
+        let rendered = crypt.to_string();
+        let body = rendered.strip_prefix(PREFIX).unwrap();
+        let parsed = Sha256Crypt::parse(body).unwrap();
+        assert!(parsed.verify(b"rotate-me"));
+        assert!(!parsed.verify(b"other"));
+    }
+}